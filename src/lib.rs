@@ -1,39 +1,102 @@
+// `EventHandle` below (behind `cfg(unix)`/`cfg(windows)`) needs `libc`
+// (unix) and `winapi` with the `synchapi`, `winbase`, `handleapi`, and
+// `winnt` features (windows) declared as dependencies in Cargo.toml
+// alongside `ftd2xx_sys`.
 use ftd2xx_sys::*;
 
-use std::{error, ffi, fmt, io, os, ptr};
+use std::{error, ffi, fmt, io, os, ptr, time::Duration};
 
-#[derive(Debug)]
-pub struct FTError {
-    status: FT_STATUS,
+/// Typed mapping of the D2XX driver's `FT_STATUS` codes.
+///
+/// Codes 1-19 from the D2XX programmer's guide are given named variants;
+/// anything else (including codes added by future driver versions) falls
+/// back to [`FTError::Other`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FTError {
+    InvalidHandle,
+    DeviceNotFound,
+    DeviceNotOpened,
+    IoError,
+    InsufficientResources,
+    InvalidParameter,
+    InvalidBaudRate,
+    DeviceNotOpenedForErase,
+    DeviceNotOpenedForWrite,
+    FailedToWriteDevice,
+    EepromReadFailed,
+    EepromWriteFailed,
+    EepromEraseFailed,
+    EepromNotPresent,
+    EepromNotProgrammed,
+    InvalidArgs,
+    NotSupported,
+    OtherError,
+    DeviceListNotReady,
+    /// Any status code not covered by a named variant above, carrying the
+    /// raw `FT_STATUS` value.
+    Other(u32),
 }
 
 impl FTError {
     fn from_raw(status: FT_STATUS) -> Option<FTError> {
-        if status == FT_OK as FT_STATUS {
-            None
-        } else {
-            Some(FTError { status })
+        match status as u32 {
+            0 => None,
+            1 => Some(FTError::InvalidHandle),
+            2 => Some(FTError::DeviceNotFound),
+            3 => Some(FTError::DeviceNotOpened),
+            4 => Some(FTError::IoError),
+            5 => Some(FTError::InsufficientResources),
+            6 => Some(FTError::InvalidParameter),
+            7 => Some(FTError::InvalidBaudRate),
+            8 => Some(FTError::DeviceNotOpenedForErase),
+            9 => Some(FTError::DeviceNotOpenedForWrite),
+            10 => Some(FTError::FailedToWriteDevice),
+            11 => Some(FTError::EepromReadFailed),
+            12 => Some(FTError::EepromWriteFailed),
+            13 => Some(FTError::EepromEraseFailed),
+            14 => Some(FTError::EepromNotPresent),
+            15 => Some(FTError::EepromNotProgrammed),
+            16 => Some(FTError::InvalidArgs),
+            17 => Some(FTError::NotSupported),
+            18 => Some(FTError::OtherError),
+            19 => Some(FTError::DeviceListNotReady),
+            other => Some(FTError::Other(other)),
         }
     }
 
-    fn raw(&self) -> u32 {
-        self.status as u32
-    }
 }
 
-impl error::Error for FTError {
-    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
-        Some(self)
-    }
-}
+impl error::Error for FTError {}
 
 impl fmt::Display for FTError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "FT_STATUS: {}", self.raw())
+        let name = match self {
+            FTError::InvalidHandle => "InvalidHandle",
+            FTError::DeviceNotFound => "DeviceNotFound",
+            FTError::DeviceNotOpened => "DeviceNotOpened",
+            FTError::IoError => "IoError",
+            FTError::InsufficientResources => "InsufficientResources",
+            FTError::InvalidParameter => "InvalidParameter",
+            FTError::InvalidBaudRate => "InvalidBaudRate",
+            FTError::DeviceNotOpenedForErase => "DeviceNotOpenedForErase",
+            FTError::DeviceNotOpenedForWrite => "DeviceNotOpenedForWrite",
+            FTError::FailedToWriteDevice => "FailedToWriteDevice",
+            FTError::EepromReadFailed => "EepromReadFailed",
+            FTError::EepromWriteFailed => "EepromWriteFailed",
+            FTError::EepromEraseFailed => "EepromEraseFailed",
+            FTError::EepromNotPresent => "EepromNotPresent",
+            FTError::EepromNotProgrammed => "EepromNotProgrammed",
+            FTError::InvalidArgs => "InvalidArgs",
+            FTError::NotSupported => "NotSupported",
+            FTError::OtherError => "OtherError",
+            FTError::DeviceListNotReady => "DeviceListNotReady",
+            FTError::Other(code) => return write!(f, "Other({})", code),
+        };
+        write!(f, "{}", name)
     }
 }
 
-// TODO: There's definitely a more elegant solution for this
 fn status_to_result(status: FT_STATUS) -> Result<()> {
     match FTError::from_raw(status) {
         None => Ok(()),
@@ -43,6 +106,61 @@ fn status_to_result(status: FT_STATUS) -> Result<()> {
 
 type Result<T> = std::result::Result<T, FTError>;
 
+const FT_OPEN_BY_SERIAL_NUMBER: u32 = 1;
+const FT_OPEN_BY_DESCRIPTION: u32 = 2;
+
+const FT_PURGE_RX: u32 = 1;
+const FT_PURGE_TX: u32 = 2;
+
+const FT_EVENT_RXCHAR: u32 = 1;
+
+/// Which event(s) woke [`Device::wait_for_rx`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Events {
+    pub rx_char: bool,
+}
+
+fn open_ex(value: &str, flags: u32) -> Result<Device> {
+    let c_value = ffi::CString::new(value).map_err(|_| FTError::InvalidParameter)?;
+    let mut info = FT_DEVICE_LIST_INFO_NODE::default();
+    unsafe {
+        status_to_result(FT_OpenEx(
+            c_value.as_ptr() as *mut ffi::c_void,
+            flags,
+            &mut info.ftHandle,
+        ))?;
+        // FT_OpenEx only returns a handle; fill in the type, ID, serial
+        // number and description so those accessors report real data
+        // instead of `default()`'s zeroes. `LocId` is not filled in and
+        // stays 0.
+        status_to_result(FT_GetDeviceInfo(
+            info.ftHandle,
+            &mut info.Type,
+            &mut info.ID,
+            info.SerialNumber.as_mut_ptr(),
+            info.Description.as_mut_ptr(),
+            ptr::null_mut(),
+        ))?;
+    }
+    info.Flags |= 0x1;
+    Ok(Device {
+        index: 0,
+        info,
+        event: None,
+    })
+}
+
+/// Opens a device by its serial number without first calling
+/// [`scan_devices`], so a known device can be reached reproducibly across
+/// reboots and USB hub reorderings.
+///
+/// The returned `Device`'s enumeration index is meaningless (it was never
+/// enumerated), so don't call [`Device::open`] on it again; use
+/// [`Device::close`] to release it instead.
+pub fn open_by_serial(serial: &str) -> Result<Device> {
+    Device::open_by_serial(serial)
+}
+
 /// Scans for any connected FTD2XX devices
 pub fn scan_devices() -> Result<Vec<Device>> {
     let mut devices = Vec::new();
@@ -64,6 +182,7 @@ pub fn scan_devices() -> Result<Vec<Device>> {
             devices.push(Device {
                 index: index as usize,
                 info: info_nodes[index as usize],
+                event: None,
             });
         }
     }
@@ -71,6 +190,95 @@ pub fn scan_devices() -> Result<Vec<Device>> {
     Ok(devices)
 }
 
+/// Number of data bits per character, for use with
+/// [`Device::set_data_characteristics`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataBits {
+    Seven,
+    Eight,
+}
+
+impl DataBits {
+    fn raw(self) -> u8 {
+        match self {
+            DataBits::Seven => 7,
+            DataBits::Eight => 8,
+        }
+    }
+}
+
+/// Number of stop bits per character, for use with
+/// [`Device::set_data_characteristics`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopBits {
+    One,
+    Two,
+}
+
+impl StopBits {
+    fn raw(self) -> u8 {
+        match self {
+            StopBits::One => 0,
+            StopBits::Two => 2,
+        }
+    }
+}
+
+/// Parity mode, for use with [`Device::set_data_characteristics`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parity {
+    None,
+    Odd,
+    Even,
+    Mark,
+    Space,
+}
+
+impl Parity {
+    fn raw(self) -> u8 {
+        match self {
+            Parity::None => 0,
+            Parity::Odd => 1,
+            Parity::Even => 2,
+            Parity::Mark => 3,
+            Parity::Space => 4,
+        }
+    }
+}
+
+/// Flow control mode, for use with [`Device::set_flow_control`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowControl {
+    None,
+    RtsCts,
+    DtrDsr,
+    XonXoff { xon: u8, xoff: u8 },
+}
+
+impl FlowControl {
+    fn raw(self) -> (u16, u8, u8) {
+        match self {
+            FlowControl::None => (0x0000, 0, 0),
+            FlowControl::RtsCts => (0x0100, 0, 0),
+            FlowControl::DtrDsr => (0x0200, 0, 0),
+            FlowControl::XonXoff { xon, xoff } => (0x0400, xon, xoff),
+        }
+    }
+}
+
+/// Device bit mode, for use with [`Device::set_bit_mode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitMode {
+    Reset = 0x00,
+    AsyncBitbang = 0x01,
+    Mpsse = 0x02,
+    SyncBitbang = 0x04,
+    McuHost = 0x08,
+    FastSerial = 0x10,
+    CbusBitbang = 0x20,
+    SyncFifo = 0x40,
+}
+
 pub struct FTProgramData {
     manufacturer: [char; 32],
     manufacturer_id: [char; 16],
@@ -108,6 +316,39 @@ impl FTProgramData {
                 .unwrap()
         }
     }
+    pub fn set_manufacturer(&mut self, value: &str) -> Result<()> {
+        set_char_buf(&mut self.manufacturer, value)
+    }
+    pub fn set_manufacturer_id(&mut self, value: &str) -> Result<()> {
+        set_char_buf(&mut self.manufacturer_id, value)
+    }
+    pub fn set_description(&mut self, value: &str) -> Result<()> {
+        set_char_buf(&mut self.description, value)
+    }
+    pub fn set_serial_number(&mut self, value: &str) -> Result<()> {
+        set_char_buf(&mut self.serial_number, value)
+    }
+}
+
+// Writes `value` into `buf` as a NUL-terminated string, leaving room for the
+// terminator. `buf.len()` is the field's real byte capacity (e.g. 32 for
+// `manufacturer`); the `get_*` accessors read this same region contiguously
+// via `CStr::from_ptr`, so we have to write through a byte view rather than
+// `[char; N]`'s 4-byte-per-element layout, or every string beyond the first
+// byte would be truncated.
+fn set_char_buf(buf: &mut [char], value: &str) -> Result<()> {
+    if value.len() >= buf.len() {
+        return Err(FTError::InvalidParameter);
+    }
+
+    let bytes = unsafe { std::slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut u8, buf.len()) };
+
+    for byte in bytes.iter_mut() {
+        *byte = 0;
+    }
+    bytes[..value.len()].copy_from_slice(value.as_bytes());
+
+    Ok(())
 }
 
 impl fmt::Display for FTProgramData {
@@ -119,6 +360,7 @@ impl fmt::Display for FTProgramData {
 pub struct Device {
     index: usize,
     info: _ft_device_list_info_node,
+    event: Option<Box<EventHandle>>,
 }
 
 impl Device {
@@ -164,9 +406,32 @@ impl Device {
         }
         Ok(bitmode)
     }
+    /// Sets the device's bit mode, unlocking GPIO-style use of the pins.
+    ///
+    /// `mask` selects which pins are outputs (`1`) vs. inputs (`0`) while
+    /// the device is in a bit-bang mode; it is ignored for modes that don't
+    /// use it (e.g. [`BitMode::Mpsse`]).
+    pub fn set_bit_mode(&mut self, mask: u8, mode: BitMode) -> Result<()> {
+        unsafe { status_to_result(FT_SetBitMode(self.info.ftHandle, mask, mode as u8)) }
+    }
     pub fn open(&mut self) -> Result<()> {
         unsafe { status_to_result(FT_Open(self.index as i32, &mut self.info.ftHandle)) }
     }
+    /// Opens a device by its serial number, bypassing enumeration order.
+    ///
+    /// Useful when the device's index would otherwise shift as other
+    /// devices are plugged in or unplugged. The returned `Device`'s
+    /// enumeration index is meaningless, so don't call [`Device::open`] on
+    /// it again; use [`Device::close`] to release it instead.
+    pub fn open_by_serial(serial: &str) -> Result<Device> {
+        open_ex(serial, FT_OPEN_BY_SERIAL_NUMBER)
+    }
+    /// Opens a device by its description string, bypassing enumeration
+    /// order. See [`Device::open_by_serial`] for the same caveat about
+    /// re-opening the returned `Device`.
+    pub fn open_by_description(description: &str) -> Result<Device> {
+        open_ex(description, FT_OPEN_BY_DESCRIPTION)
+    }
     pub fn close(&mut self) -> Result<()> {
         unsafe {
             status_to_result(FT_Close(self.info.ftHandle))?;
@@ -179,6 +444,53 @@ impl Device {
     pub fn set_baud_rate(&mut self, rate: u32) -> Result<()> {
         unsafe { status_to_result(FT_SetBaudRate(self.info.ftHandle, rate)) }
     }
+    pub fn set_data_characteristics(
+        &mut self,
+        data_bits: DataBits,
+        stop_bits: StopBits,
+        parity: Parity,
+    ) -> Result<()> {
+        unsafe {
+            status_to_result(FT_SetDataCharacteristics(
+                self.info.ftHandle,
+                data_bits.raw(),
+                stop_bits.raw(),
+                parity.raw(),
+            ))
+        }
+    }
+    pub fn set_flow_control(&mut self, flow_control: FlowControl) -> Result<()> {
+        let (flow, xon, xoff) = flow_control.raw();
+        unsafe { status_to_result(FT_SetFlowControl(self.info.ftHandle, flow, xon, xoff)) }
+    }
+    /// Flushes stale bytes out of the device's receive and/or transmit
+    /// buffers.
+    pub fn purge(&mut self, rx: bool, tx: bool) -> Result<()> {
+        let mut mask = 0;
+        if rx {
+            mask |= FT_PURGE_RX;
+        }
+        if tx {
+            mask |= FT_PURGE_TX;
+        }
+        unsafe { status_to_result(FT_Purge(self.info.ftHandle, mask)) }
+    }
+    /// Bounds how long `read`/`write` are allowed to block, in milliseconds.
+    ///
+    /// Without this the [`io::Read`] impl can block forever waiting for
+    /// bytes that never arrive.
+    pub fn set_timeouts(&mut self, read_ms: u32, write_ms: u32) -> Result<()> {
+        unsafe { status_to_result(FT_SetTimeouts(self.info.ftHandle, read_ms, write_ms)) }
+    }
+    /// Returns the number of bytes currently available in the receive
+    /// queue, so a caller can size a read without blocking.
+    pub fn queue_status(&self) -> Result<u32> {
+        let mut bytes_available = 0;
+        unsafe {
+            status_to_result(FT_GetQueueStatus(self.info.ftHandle, &mut bytes_available))?;
+        }
+        Ok(bytes_available)
+    }
     pub fn query_program_data(&self) -> Result<FTProgramData> {
         let mut data = FTProgramData {
             // TODO: There's got to be a better way to initialize these...
@@ -200,23 +512,188 @@ impl Device {
         }
         Ok(data)
     }
+    pub fn program_data(&mut self, data: &FTProgramData) -> Result<()> {
+        let mut inner = data.inner;
+        inner.Signature1 = 0x00000000;
+        inner.Signature2 = 0xffffffff;
+        inner.Version = 0x00000005;
+        unsafe { status_to_result(FT_EE_Program(self.info.ftHandle, &mut inner)) }
+    }
+    /// Registers for `FT_EVENT_RXCHAR` notification, so [`Device::wait_for_rx`]
+    /// can sleep until the driver signals new bytes instead of spin-reading
+    /// [`Device::queue_status`].
+    ///
+    /// Called automatically by [`Device::wait_for_rx`] on first use. Safe to
+    /// call again later (e.g. to re-arm after a platform event handle was
+    /// somehow invalidated); any prior registration is cleared first so the
+    /// driver is never left holding a pointer to a freed handle.
+    pub fn set_event_notification(&mut self) -> Result<()> {
+        self.clear_event_notification()?;
+
+        let mut handle = EventHandle::new();
+        unsafe {
+            status_to_result(FT_SetEventNotification(
+                self.info.ftHandle,
+                FT_EVENT_RXCHAR,
+                handle.as_raw(),
+            ))?;
+        }
+
+        self.event = Some(handle);
+
+        Ok(())
+    }
+    // Tells the driver to stop notifying the current event handle, if any,
+    // before dropping it, so it never holds a pointer to freed memory.
+    fn clear_event_notification(&mut self) -> Result<()> {
+        if let Some(_old_handle) = self.event.take() {
+            unsafe {
+                status_to_result(FT_SetEventNotification(self.info.ftHandle, 0, ptr::null_mut()))?;
+            }
+        }
+        Ok(())
+    }
+    /// Blocks the calling thread until bytes arrive or `timeout` elapses.
+    ///
+    /// This lets a thread sleep on the device's event primitive rather than
+    /// busy-polling [`Device::queue_status`], so it can integrate the device
+    /// into its own event loop.
+    pub fn wait_for_rx(&mut self, timeout: Duration) -> Result<Events> {
+        if self.event.is_none() {
+            self.set_event_notification()?;
+        }
+
+        let signalled = self.event.as_mut().unwrap().wait(timeout);
+
+        Ok(Events { rx_char: signalled })
+    }
+}
+
+impl Drop for Device {
+    fn drop(&mut self) {
+        // Tell the driver to stop notifying our event handle before it gets
+        // freed; ignore the result since there's nothing to do about a
+        // failure here (e.g. the handle is already closed).
+        let _ = self.clear_event_notification();
+    }
+}
+
+// Mirrors the D2XX driver's notion of a waitable event: on Windows a plain
+// auto-reset event object, on Unix the `EVENT_HANDLE { pthread_cond_t
+// eCondVar; pthread_mutex_t eMutex; int iVar; }` layout the Linux/macOS D2XX
+// driver expects to find at the pointer handed to `FT_SetEventNotification`
+// — the driver locks `mutex`, sets `i_var`, and signals `cond`, so the Unix
+// struct's field order and size must match exactly. The driver signals it
+// from its own background thread, so it must live at a stable address for
+// as long as the notification is registered, hence the `Box`.
+#[cfg(windows)]
+struct EventHandle {
+    handle: winapi::um::winnt::HANDLE,
+}
+
+#[cfg(windows)]
+impl EventHandle {
+    fn new() -> Box<EventHandle> {
+        let handle =
+            unsafe { winapi::um::synchapi::CreateEventW(ptr::null_mut(), 0, 0, ptr::null()) };
+        Box::new(EventHandle { handle })
+    }
+
+    fn as_raw(&mut self) -> *mut ffi::c_void {
+        self.handle as *mut ffi::c_void
+    }
+
+    fn wait(&mut self, timeout: Duration) -> bool {
+        let millis = timeout.as_millis().min(u128::from(u32::MAX)) as u32;
+        unsafe {
+            winapi::um::synchapi::WaitForSingleObject(self.handle, millis)
+                == winapi::um::winbase::WAIT_OBJECT_0
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for EventHandle {
+    fn drop(&mut self) {
+        unsafe {
+            winapi::um::handleapi::CloseHandle(self.handle);
+        }
+    }
+}
+
+#[cfg(unix)]
+#[repr(C)]
+struct EventHandle {
+    cond: libc::pthread_cond_t,
+    mutex: libc::pthread_mutex_t,
+    i_var: os::raw::c_int,
+}
+
+#[cfg(unix)]
+impl EventHandle {
+    fn new() -> Box<EventHandle> {
+        let mut handle = Box::new(EventHandle {
+            cond: unsafe { std::mem::zeroed() },
+            mutex: unsafe { std::mem::zeroed() },
+            i_var: 0,
+        });
+        unsafe {
+            libc::pthread_cond_init(&mut handle.cond, ptr::null());
+            libc::pthread_mutex_init(&mut handle.mutex, ptr::null());
+        }
+        handle
+    }
+
+    fn as_raw(&mut self) -> *mut ffi::c_void {
+        self as *mut EventHandle as *mut ffi::c_void
+    }
+
+    fn wait(&mut self, timeout: Duration) -> bool {
+        unsafe {
+            let mut deadline: libc::timespec = std::mem::zeroed();
+            libc::clock_gettime(libc::CLOCK_REALTIME, &mut deadline);
+            deadline.tv_sec += timeout.as_secs() as libc::time_t;
+            deadline.tv_nsec += i64::from(timeout.subsec_nanos());
+            if deadline.tv_nsec >= 1_000_000_000 {
+                deadline.tv_sec += 1;
+                deadline.tv_nsec -= 1_000_000_000;
+            }
+
+            libc::pthread_mutex_lock(&mut self.mutex);
+            let mut result = 0;
+            while self.i_var == 0 && result == 0 {
+                result = libc::pthread_cond_timedwait(&mut self.cond, &mut self.mutex, &deadline);
+            }
+            let signalled = self.i_var != 0;
+            self.i_var = 0;
+            libc::pthread_mutex_unlock(&mut self.mutex);
+            signalled
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for EventHandle {
+    fn drop(&mut self) {
+        unsafe {
+            libc::pthread_cond_destroy(&mut self.cond);
+            libc::pthread_mutex_destroy(&mut self.mutex);
+        }
+    }
 }
 
 impl io::Read for Device {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         unsafe {
             let mut bytes_read = 0;
-            if status_to_result(FT_Read(
+            match status_to_result(FT_Read(
                 self.info.ftHandle,
                 buf.as_mut_ptr() as *mut ffi::c_void,
                 buf.len() as u32,
                 &mut bytes_read,
-            ))
-            .is_ok()
-            {
-                Ok(bytes_read as usize)
-            } else {
-                Err(io::Error::from(io::ErrorKind::Other))
+            )) {
+                Ok(()) => Ok(bytes_read as usize),
+                Err(err) => Err(io::Error::new(io::ErrorKind::Other, err)),
             }
         }
     }
@@ -226,17 +703,14 @@ impl io::Write for Device {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         unsafe {
             let mut bytes_written = 0;
-            if status_to_result(FT_Write(
+            match status_to_result(FT_Write(
                 self.info.ftHandle,
                 buf.as_ptr() as *mut ffi::c_void,
                 buf.len() as u32,
                 &mut bytes_written,
-            ))
-            .is_ok()
-            {
-                Ok(bytes_written as usize)
-            } else {
-                Err(io::Error::from(io::ErrorKind::Other))
+            )) {
+                Ok(()) => Ok(bytes_written as usize),
+                Err(err) => Err(io::Error::new(io::ErrorKind::Other, err)),
             }
         }
     }